@@ -0,0 +1,20 @@
+//! DNS resolution.
+//!
+//! By default, reqwest uses a pluggable [`Resolve`] trait so that lookups
+//! can be satisfied by something other than the operating system's own
+//! resolver. The `trust-dns` feature enables [`TrustDnsResolver`], a
+//! resolver built on the `trust-dns-resolver` crate that is independent
+//! of the platform's resolver and understands a richer set of upstream
+//! configurations (including encrypted transports).
+
+pub use resolve::{Addrs, Name, Resolve, Resolving};
+pub(crate) use resolve::{DnsResolverWithOverrides, DynResolver};
+
+#[cfg(feature = "trust-dns")]
+pub(crate) use trust_dns::TrustDnsResolver;
+#[cfg(feature = "trust-dns")]
+pub(crate) use trust_dns::{EncryptedDnsServer, EncryptedDnsTransport};
+
+mod resolve;
+#[cfg(feature = "trust-dns")]
+mod trust_dns;
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{self, Poll};
+
+use hyper::client::connect::dns::Name as HyperName;
+use hyper::service::Service;
+
+use crate::error::BoxError;
+
+/// Alias for an `Iterator` trait object over `SocketAddr`.
+pub type Addrs = Box<dyn Iterator<Item = SocketAddr> + Send>;
+
+/// Alias for the `Future` type returned by a DNS resolver.
+pub type Resolving = Pin<Box<dyn Future<Output = Result<Addrs, BoxError>> + Send>>;
+
+/// Trait for customizing DNS resolution in reqwest.
+pub trait Resolve: Send + Sync {
+    /// Performs DNS resolution on a `Name`.
+    fn resolve(&self, name: Name) -> Resolving;
+}
+
+/// A name to be resolved by a `Resolve` implementation.
+#[derive(Debug)]
+pub struct Name(HyperName);
+
+impl Name {
+    /// View this name as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+pub(crate) struct DynResolver {
+    resolver: Arc<dyn Resolve>,
+}
+
+impl DynResolver {
+    pub(crate) fn new(resolver: Arc<dyn Resolve>) -> Self {
+        Self { resolver }
+    }
+}
+
+impl Service<HyperName> for DynResolver {
+    type Response = Addrs;
+    type Error = BoxError;
+    type Future = Resolving;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: HyperName) -> Self::Future {
+        self.resolver.resolve(Name(name))
+    }
+}
+
+/// Wraps a `Resolve` with a static hostname-to-addresses map, consulted
+/// before falling through to the inner resolver.
+///
+/// This backs `ClientBuilder::resolve`/`resolve_to_addrs` and lets a
+/// caller pin specific hostnames to specific addresses (e.g. pointing a
+/// hostname at `127.0.0.1` in tests) without disturbing TLS SNI or
+/// certificate validation, which still operate on the original hostname.
+pub(crate) struct DnsResolverWithOverrides {
+    dns_resolver: Arc<dyn Resolve>,
+    overrides: Arc<HashMap<String, Vec<SocketAddr>>>,
+}
+
+impl DnsResolverWithOverrides {
+    pub(crate) fn new(
+        dns_resolver: Arc<dyn Resolve>,
+        overrides: HashMap<String, Vec<SocketAddr>>,
+    ) -> Self {
+        DnsResolverWithOverrides {
+            dns_resolver,
+            overrides: Arc::new(overrides),
+        }
+    }
+}
+
+impl Resolve for DnsResolverWithOverrides {
+    fn resolve(&self, name: Name) -> Resolving {
+        if let Some(addrs) = self.overrides.get(name.as_str()) {
+            let addrs = addrs.clone();
+            Box::pin(async move {
+                let addrs: Addrs = Box::new(addrs.into_iter());
+                Ok(addrs)
+            })
+        } else {
+            self.dns_resolver.resolve(name)
+        }
+    }
+}
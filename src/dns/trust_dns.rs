@@ -0,0 +1,297 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, OnceCell};
+use trust_dns_resolver::{
+    config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts},
+    lookup_ip::LookupIpIntoIter,
+    system_conf, AsyncResolver, TokioConnection, TokioConnectionProvider,
+};
+
+use super::{Addrs, Name, Resolve, Resolving};
+use crate::error::BoxError;
+
+type SharedResolver = Arc<AsyncResolver<TokioConnection, TokioConnectionProvider>>;
+
+lazy_static! {
+    static ref SYSTEM_CONF: io::Result<(ResolverConfig, ResolverOpts)> =
+        system_conf::read_system_conf().map_err(io::Error::from);
+}
+
+/// The single process-wide resolver behind `TrustDnsResolver::shared`,
+/// lazily built on first use and then reused (and its lookup cache kept
+/// warm) by every `Client` that opted in, instead of each `Client`
+/// paying for its own cold cache.
+static GLOBAL_RESOLVER: OnceCell<SharedResolver> = OnceCell::const_new();
+
+#[derive(Clone)]
+pub(crate) struct TrustDnsResolver {
+    state: Arc<Mutex<State>>,
+    happy_eyeballs: Option<HappyEyeballs>,
+}
+
+/// The address family that a [`HappyEyeballs`] reordering starts with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AddressFamily {
+    V4,
+    V6,
+}
+
+/// How `TrustDnsResolver` reorders a lookup's addresses before returning
+/// them, per the interleaving algorithm of RFC 8305 ("Happy Eyeballs").
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum HappyEyeballs {
+    /// Interleave the two address families, starting with whichever
+    /// family the resolver happened to return first.
+    Auto,
+    /// Interleave the two address families, always starting with the
+    /// given family.
+    Prefer(AddressFamily),
+}
+
+enum State {
+    Init,
+    InitWithConfig {
+        config: ResolverConfig,
+        opts: ResolverOpts,
+    },
+    Shared {
+        opts: Option<ResolverOpts>,
+    },
+    Ready(SharedResolver),
+}
+
+impl TrustDnsResolver {
+    fn from_state(state: State) -> Self {
+        TrustDnsResolver {
+            state: Arc::new(Mutex::new(state)),
+            happy_eyeballs: Some(HappyEyeballs::Auto),
+        }
+    }
+
+    pub(crate) fn new() -> io::Result<Self> {
+        SYSTEM_CONF.as_ref().map_err(|e| {
+            io::Error::new(e.kind(), format!("error reading DNS system conf: {}", e))
+        })?;
+
+        // At this stage, we might not have been called in the context of a
+        // Tokio Runtime, so we must delay the actual construction of the
+        // resolver.
+        Ok(Self::from_state(State::Init))
+    }
+
+    pub(crate) fn with_config(config: ResolverConfig, opts: ResolverOpts) -> Self {
+        Self::from_state(State::InitWithConfig { config, opts })
+    }
+
+    /// Builds a resolver that sends all queries to `servers` over an
+    /// encrypted transport (DNS-over-TLS or DNS-over-HTTPS), bypassing
+    /// the system configuration entirely.
+    ///
+    /// Requires the `trust-dns-resolver` crate to be built with its `dns-over-tls`
+    /// and/or `dns-over-https` features enabled, matching the transports
+    /// requested in `servers`.
+    pub(crate) fn with_encrypted_servers(
+        servers: Vec<EncryptedDnsServer>,
+        opts: ResolverOpts,
+    ) -> Self {
+        let mut config = ResolverConfig::new();
+        for server in servers {
+            let protocol = match server.transport {
+                EncryptedDnsTransport::Tls => Protocol::Tls,
+                EncryptedDnsTransport::Https => Protocol::Https,
+            };
+            config.add_name_server(NameServerConfig {
+                socket_addr: server.socket_addr,
+                protocol,
+                tls_dns_name: Some(server.tls_dns_name),
+                trust_nx_responses: true,
+                bind_addr: None,
+            });
+        }
+        Self::with_config(config, opts)
+    }
+
+    /// Builds a resolver backed by a single, process-wide resolver,
+    /// sharing its lookup cache with every other `Client` that also
+    /// opted into sharing.
+    ///
+    /// The global resolver is constructed once, from the system
+    /// configuration, on the first lookup performed by any such
+    /// `Client`; `opts` is only honored for that first construction and
+    /// is ignored by every `Client` that loses the race to initialize
+    /// it. Pass `opts` to clamp the cache size or negative/positive TTLs
+    /// away from the system's defaults.
+    pub(crate) fn shared(opts: Option<ResolverOpts>) -> Self {
+        Self::from_state(State::Shared { opts })
+    }
+
+    /// Sets how resolved addresses are reordered before being returned,
+    /// per RFC 8305 ("Happy Eyeballs"). Pass `None` to return addresses
+    /// in the order the resolver produced them, unmodified.
+    pub(crate) fn happy_eyeballs(mut self, mode: Option<HappyEyeballs>) -> Self {
+        self.happy_eyeballs = mode;
+        self
+    }
+}
+
+/// The encrypted transport used to reach an [`EncryptedDnsServer`].
+#[derive(Clone, Debug)]
+pub(crate) enum EncryptedDnsTransport {
+    /// DNS-over-TLS (RFC 7858), conventionally served on port 853.
+    Tls,
+    /// DNS-over-HTTPS (RFC 8484), conventionally served on port 443.
+    Https,
+}
+
+/// A single upstream name server reachable over an encrypted transport.
+///
+/// `tls_dns_name` is the name presented in the server's TLS certificate
+/// and is validated independently of the hostname being looked up.
+#[derive(Clone, Debug)]
+pub(crate) struct EncryptedDnsServer {
+    pub(crate) socket_addr: SocketAddr,
+    pub(crate) tls_dns_name: String,
+    pub(crate) transport: EncryptedDnsTransport,
+}
+
+impl Resolve for TrustDnsResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        let happy_eyeballs = self.happy_eyeballs;
+        Box::pin(async move {
+            let mut lock = resolver.state.lock().await;
+
+            let resolver = match &*lock {
+                State::Init => {
+                    let resolver = new_resolver(tokio::runtime::Handle::current()).await?;
+                    *lock = State::Ready(resolver.clone());
+                    resolver
+                }
+                State::InitWithConfig { config, opts } => {
+                    let resolver = new_resolver_with_config(
+                        tokio::runtime::Handle::current(),
+                        config.clone(),
+                        opts.clone(),
+                    )
+                    .await?;
+                    *lock = State::Ready(resolver.clone());
+                    resolver
+                }
+                State::Shared { opts } => {
+                    let resolver =
+                        global_resolver(tokio::runtime::Handle::current(), opts.clone()).await?;
+                    *lock = State::Ready(resolver.clone());
+                    resolver
+                }
+                State::Ready(resolver) => resolver.clone(),
+            };
+
+            // Don't keep lock once the resolver is constructed, otherwise
+            // only one lookup could be done at a time.
+            drop(lock);
+
+            let lookup = resolver.lookup_ip(name.as_str()).await?;
+            let addrs: Addrs = match happy_eyeballs {
+                Some(mode) => interleave(lookup.into_iter(), mode),
+                None => Box::new(lookup.into_iter()),
+            };
+            Ok(addrs)
+        })
+    }
+}
+
+/// Reorders `addrs` per the interleaving algorithm of RFC 8305 ("Happy
+/// Eyeballs"): partitions them by address family, then alternates
+/// between the two families, starting with `mode`'s preferred family.
+/// Each family's relative order is preserved, so a host with only one
+/// family is returned exactly as the resolver produced it.
+fn interleave(addrs: LookupIpIntoIter, mode: HappyEyeballs) -> Addrs {
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    let mut first_family = None;
+
+    for addr in addrs {
+        let family = if addr.is_ipv6() {
+            AddressFamily::V6
+        } else {
+            AddressFamily::V4
+        };
+        // `Auto` takes whichever family the resolver returned first.
+        let first_family = *first_family.get_or_insert(match mode {
+            HappyEyeballs::Prefer(family) => family,
+            HappyEyeballs::Auto => family,
+        });
+        if family == first_family {
+            first.push(addr);
+        } else {
+            second.push(addr);
+        }
+    }
+
+    let mut interleaved = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (Some(a), Some(b)) => {
+                interleaved.push(a);
+                interleaved.push(b);
+            }
+            (Some(a), None) => {
+                interleaved.push(a);
+                interleaved.extend(first.by_ref());
+                break;
+            }
+            (None, Some(b)) => {
+                interleaved.push(b);
+                interleaved.extend(second.by_ref());
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+    Box::new(interleaved.into_iter())
+}
+
+/// Takes a `Handle` argument as an indicator that it must be called from
+/// within the context of a Tokio runtime.
+async fn new_resolver(handle: tokio::runtime::Handle) -> Result<SharedResolver, BoxError> {
+    let (config, opts) = SYSTEM_CONF
+        .as_ref()
+        .expect("can't construct TrustDnsResolver if SYSTEM_CONF is error")
+        .clone();
+    let resolver = AsyncResolver::new(config, opts, handle).await?;
+    Ok(Arc::new(resolver))
+}
+
+async fn new_resolver_with_config(
+    handle: tokio::runtime::Handle,
+    config: ResolverConfig,
+    opts: ResolverOpts,
+) -> Result<SharedResolver, BoxError> {
+    let resolver = AsyncResolver::new(config, opts, handle).await?;
+    Ok(Arc::new(resolver))
+}
+
+/// Returns the single process-wide resolver, constructing it from the
+/// system configuration on the first call. `opts` only has an effect on
+/// that first call; once the global resolver is initialized, it is
+/// reused (cache and all) by every subsequent caller regardless of the
+/// `opts` they pass.
+async fn global_resolver(
+    handle: tokio::runtime::Handle,
+    opts: Option<ResolverOpts>,
+) -> Result<SharedResolver, BoxError> {
+    let resolver = GLOBAL_RESOLVER
+        .get_or_try_init(|| async {
+            let (config, default_opts) = SYSTEM_CONF
+                .as_ref()
+                .map_err(|e| io::Error::new(e.kind(), format!("{}", e)))?
+                .clone();
+            new_resolver_with_config(handle, config, opts.unwrap_or(default_opts)).await
+        })
+        .await?;
+    Ok(resolver.clone())
+}